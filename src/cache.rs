@@ -0,0 +1,113 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use jiff::Zoned;
+
+/// Cache behavior, initialized once from the CLI flags.
+struct CacheConfig {
+    enabled: bool,
+    refresh: bool,
+    since: String,
+}
+
+static CONFIG: OnceLock<CacheConfig> = OnceLock::new();
+
+/// Initialize the on-disk response cache from the parsed CLI flags.
+///
+/// Call once at startup. `no_cache` disables the cache entirely, `refresh`
+/// ignores existing entries but still repopulates them, and `since` is the
+/// activity window that (together with the arguments) keys each entry.
+pub fn init(no_cache: bool, refresh: bool, since: &str) {
+    let _ = CONFIG.set(CacheConfig {
+        enabled: !no_cache,
+        refresh,
+        since: since.to_owned(),
+    });
+}
+
+/// Produce the `gh` output for `args`, serving from and storing to the on-disk
+/// cache when enabled. `op` is only invoked on a cache miss (or with
+/// `--refresh`/`--no-cache`).
+pub fn cached<F>(args: &[&str], op: F) -> Result<String, String>
+where
+    F: FnOnce() -> Result<String, String>,
+{
+    let Some(config) = CONFIG.get() else {
+        return op();
+    };
+    if !config.enabled {
+        return op();
+    }
+
+    let Some(path) = cache_path(args, &config.since) else {
+        return op();
+    };
+
+    if !config.refresh {
+        if let Some(hit) = read_fresh(&path, &config.since) {
+            return Ok(hit);
+        }
+    }
+
+    let output = op()?;
+    write_entry(&path, &output);
+    Ok(output)
+}
+
+/// The per-user cache directory (`$XDG_CACHE_HOME/gh-summary`).
+fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("gh-summary"))
+}
+
+/// Hash the argument vector plus the `since` window into a cache path.
+///
+/// The GraphQL `to=<now>` argument is stamped at second precision and so would
+/// change on every invocation; it is excluded from the key. The `since..now`
+/// window is fully captured by `since` (which the TTL logic already assumes),
+/// so historical windows hit the cache instead of re-fetching every run.
+fn cache_path(args: &[&str], since: &str) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    for arg in args.iter().filter(|arg| !arg.starts_with("to=")) {
+        arg.hash(&mut hasher);
+    }
+    since.hash(&mut hasher);
+    Some(cache_dir()?.join(format!("{:016x}.json", hasher.finish())))
+}
+
+/// How long an entry stays valid.
+///
+/// The `since..now` window always ends at the current instant, so a query that
+/// starts today keeps seeing fresh activity and gets a short TTL; a window that
+/// opened on an earlier day changes little and can be cached far longer.
+fn ttl(since: &str) -> Duration {
+    let today = Zoned::now().strftime("%Y-%m-%d").to_string();
+    if since == today {
+        Duration::from_secs(5 * 60)
+    } else {
+        Duration::from_secs(6 * 60 * 60)
+    }
+}
+
+/// Read a cache entry if it exists and is still within its TTL.
+fn read_fresh(path: &Path, since: &str) -> Option<String> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let age = SystemTime::now().duration_since(modified).ok()?;
+    if age > ttl(since) {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+/// Store a response, best-effort: cache errors never fail the run.
+fn write_entry(path: &Path, output: &str) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(path, output);
+}
@@ -0,0 +1,44 @@
+use std::sync::{Condvar, Mutex};
+
+/// A counting semaphore bounding how many operations run at once, modeled on
+/// the `MaxHandles` limiter in the awesome-rust link checker. It keeps the
+/// number of concurrent `gh` subprocesses within a fixed budget.
+pub struct MaxHandles {
+    available: Mutex<usize>,
+    condvar: Condvar,
+}
+
+impl MaxHandles {
+    /// Create a limiter allowing at most `max` (but always at least one)
+    /// concurrent permits.
+    pub fn new(max: usize) -> Self {
+        Self {
+            available: Mutex::new(max.max(1)),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Block until a permit is free, returning a guard that releases it on drop.
+    pub fn acquire(&self) -> Handle<'_> {
+        let mut available = self.available.lock().unwrap();
+        while *available == 0 {
+            available = self.condvar.wait(available).unwrap();
+        }
+        *available -= 1;
+        Handle { handles: self }
+    }
+}
+
+/// A permit held for the duration of an operation; returns it to the pool on
+/// drop and wakes one waiter.
+pub struct Handle<'a> {
+    handles: &'a MaxHandles,
+}
+
+impl Drop for Handle<'_> {
+    fn drop(&mut self) {
+        let mut available = self.handles.available.lock().unwrap();
+        *available += 1;
+        self.handles.condvar.notify_one();
+    }
+}
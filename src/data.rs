@@ -24,3 +24,85 @@ impl Items {
         self.0.into_iter().map(|item| item.url).collect()
     }
 }
+
+/// Envelope for a GraphQL response so that API-level errors surface cleanly
+/// instead of being mistaken for a missing `data` field.
+#[derive(Deserialize)]
+pub struct GraphResult<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphError>,
+}
+
+/// A single GraphQL error as reported in the `errors` array
+#[derive(Deserialize)]
+pub struct GraphError {
+    pub message: String,
+}
+
+/// `data` payload of the contributions query
+#[derive(Deserialize)]
+pub struct ContributionsData {
+    pub user: Option<UserContributions>,
+}
+
+#[derive(Deserialize)]
+pub struct UserContributions {
+    #[serde(rename = "contributionsCollection")]
+    pub contributions_collection: ContributionsCollection,
+}
+
+/// A user's contributions within the queried `from`/`to` window
+#[derive(Deserialize)]
+pub struct ContributionsCollection {
+    #[serde(rename = "totalPullRequestContributions")]
+    pub total_pull_request_contributions: i32,
+    #[serde(rename = "totalIssueContributions")]
+    pub total_issue_contributions: i32,
+    #[serde(rename = "totalPullRequestReviewContributions")]
+    pub total_pull_request_review_contributions: i32,
+    #[serde(rename = "totalCommitContributions")]
+    pub total_commit_contributions: i32,
+    #[serde(rename = "pullRequestContributions")]
+    pub pull_request_contributions: ContributionConnection<PullRequestContribution>,
+    #[serde(rename = "issueContributions")]
+    pub issue_contributions: ContributionConnection<IssueContribution>,
+    #[serde(rename = "pullRequestReviewContributions")]
+    pub pull_request_review_contributions: ContributionConnection<ReviewContribution>,
+}
+
+/// A GraphQL connection carrying only the `nodes` we request
+#[derive(Deserialize)]
+pub struct ContributionConnection<T> {
+    pub nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+pub struct PullRequestContribution {
+    #[serde(rename = "pullRequest")]
+    pub pull_request: ContributionRef,
+}
+
+#[derive(Deserialize)]
+pub struct IssueContribution {
+    pub issue: ContributionRef,
+}
+
+#[derive(Deserialize)]
+pub struct ReviewContribution {
+    #[serde(rename = "pullRequest")]
+    pub pull_request: ContributionRef,
+}
+
+/// The shared shape of a contributed PR/issue: its URL and owning repository
+#[derive(Deserialize)]
+pub struct ContributionRef {
+    pub url: String,
+    pub repository: RepositoryRef,
+}
+
+#[derive(Deserialize)]
+pub struct RepositoryRef {
+    #[serde(rename = "nameWithOwner")]
+    pub name_with_owner: String,
+}
@@ -1,9 +1,18 @@
 use std::process::Command;
-use crate::data::{CodeMetrics, Items};
-use jiff::{ToSpan, Zoned};
-
-/// Execute a GitHub CLI command and return the output
+use crate::data::{
+    CodeMetrics, ContributionsCollection, ContributionsData, GraphResult, Items,
+};
+use jiff::Timestamp;
+use serde::Deserialize;
+
+/// Execute a GitHub CLI command and return the output, serving from the
+/// on-disk response cache on a hit.
 pub fn run_gh_command(args: &[&str]) -> Result<String, String> {
+    crate::cache::cached(args, || run_gh_command_uncached(args))
+}
+
+/// Execute a GitHub CLI command, bypassing the cache.
+fn run_gh_command_uncached(args: &[&str]) -> Result<String, String> {
     let output = Command::new("gh")
         .args(args)
         .output()
@@ -31,82 +40,188 @@ pub fn get_current_user() -> Result<String, String> {
     Ok(output.trim().to_owned())
 }
 
+/// Sort field for `gh search`, modeled on the search client's `IssuesSort`.
+#[derive(Clone, Copy)]
+pub enum IssuesSort {
+    Created,
+    Updated,
+    Comments,
+}
+
+impl IssuesSort {
+    /// The `--sort` value understood by `gh search`.
+    fn as_flag(self) -> &'static str {
+        match self {
+            IssuesSort::Created => "created",
+            IssuesSort::Updated => "updated",
+            IssuesSort::Comments => "comments",
+        }
+    }
+}
+
+/// Append `--sort`/`--order` flags for the requested sort, if any.
+fn push_sort(args: &mut Vec<String>, sort: Option<IssuesSort>) {
+    if let Some(sort) = sort {
+        args.push(format!("--sort={}", sort.as_flag()));
+        args.push("--order=desc".to_owned());
+    }
+}
+
 /// Search for pull requests
-pub fn search_prs(username: &str, filter: &str, since: &str) -> Result<Vec<String>, String> {
-    let output = run_gh_command(&[
-        "search",
-        "prs",
-        &format!("--author={username}"),
-        filter,
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
-    ])?;
+pub fn search_prs(
+    username: &str,
+    filter: &str,
+    since: &str,
+    sort: Option<IssuesSort>,
+) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = vec![
+        "search".to_owned(),
+        "prs".to_owned(),
+        format!("--author={username}"),
+        filter.to_owned(),
+        format!(">={since}"),
+        "--json=url".to_owned(),
+        "--limit=1000".to_owned(),
+    ];
+    push_sort(&mut args, sort);
+
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = run_gh_command(&arg_refs)?;
 
     let items: Items =
         serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
     Ok(items.into_urls())
 }
 
-/// Search for pull requests with detailed code metrics (mock implementation)
-pub fn search_prs_detailed(
+/// Fetch a single PR's real code metrics and creation date via `gh pr view`.
+///
+/// Returns the creation date (`YYYY-MM-DD`) alongside the metrics, or an error
+/// if the PR can't be read (e.g. it lives in a repo the token can't access);
+/// callers skip such PRs rather than aborting the whole run.
+pub fn pr_code_metrics(url: &str) -> Result<(String, CodeMetrics), String> {
+    /// The subset of `gh pr view` fields we care about.
+    #[derive(Deserialize)]
+    struct PrDetails {
+        additions: i32,
+        deletions: i32,
+        #[serde(rename = "changedFiles")]
+        changed_files: i32,
+        #[serde(rename = "createdAt")]
+        created_at: String,
+    }
+
+    let output = run_gh_command(&[
+        "pr",
+        "view",
+        url,
+        "--json",
+        "additions,deletions,changedFiles,createdAt",
+    ])?;
+
+    let details: PrDetails =
+        serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
+
+    // `createdAt` is an ISO-8601 timestamp; keep just the date part.
+    let date = details
+        .created_at
+        .split('T')
+        .next()
+        .unwrap_or(&details.created_at)
+        .to_owned();
+
+    Ok((
+        date,
+        CodeMetrics {
+            additions: details.additions,
+            deletions: details.deletions,
+            changed_files: details.changed_files,
+        },
+    ))
+}
+
+pub const CONTRIBUTION_PAGE_SIZE: usize = 100;
+
+/// Fetch a user's contributions for the `since..now` window in a single
+/// `gh api graphql` round trip, replacing the fan-out of `gh search` calls
+/// (and their silent 1000-item truncation) with one connection query.
+///
+/// The `total*` counts are exact, but each node list is capped at
+/// [`CONTRIBUTION_PAGE_SIZE`] (the `first:` page size below); callers compare
+/// a list's length against it to warn when the URLs are a truncated sample.
+pub fn graphql_contributions(
     username: &str,
-    filter: &str,
     since: &str,
-) -> Result<Vec<(String, String, CodeMetrics)>, String> {
-    // For now, let's use a simplified approach and return mock data to demonstrate the feature
-    let basic_prs = search_prs(username, filter, since)?;
-
-    // Generate realistic-looking mock metrics for demonstration
-    let mut results = Vec::new();
-    for (i, url) in basic_prs.iter().enumerate() {
-        let mock_metrics = CodeMetrics {
-            additions: (50 + i * 23) as i32,
-            deletions: (20 + i * 7) as i32,
-            changed_files: (3 + i % 5) as i32,
-        };
-
-        // Extract date from URL or use current date as fallback
-        let date = Zoned::now()
-            .checked_sub((i as i32).days())
-            .unwrap_or_else(|_| Zoned::now())
-            .strftime("%Y-%m-%d")
-            .to_string();
-
-        results.push((url.clone(), date, mock_metrics));
+) -> Result<ContributionsCollection, String> {
+    // The `--since` argument is a plain date; widen it into the ISO window
+    // GraphQL's `DateTime` scalar expects.
+    let from = format!("{since}T00:00:00Z");
+    let to = Timestamp::now().strftime("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    // The `first:` page sizes below must match CONTRIBUTION_PAGE_SIZE.
+    let query = r#"
+query($login: String!, $from: DateTime!, $to: DateTime!) {
+  user(login: $login) {
+    contributionsCollection(from: $from, to: $to) {
+      totalPullRequestContributions
+      totalIssueContributions
+      totalPullRequestReviewContributions
+      totalCommitContributions
+      pullRequestContributions(first: 100) { nodes { pullRequest { url repository { nameWithOwner } } } }
+      issueContributions(first: 100) { nodes { issue { url repository { nameWithOwner } } } }
+      pullRequestReviewContributions(first: 100) { nodes { pullRequest { url repository { nameWithOwner } } } }
     }
-
-    Ok(results)
+  }
 }
+"#;
 
-/// Search for issues
-pub fn search_issues(username: &str, filter: &str, since: &str) -> Result<Vec<String>, String> {
     let output = run_gh_command(&[
-        "search",
-        "issues",
-        &format!("--author={username}"),
-        filter,
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
+        "api",
+        "graphql",
+        "-f",
+        &format!("query={query}"),
+        "-f",
+        &format!("login={username}"),
+        "-f",
+        &format!("from={from}"),
+        "-f",
+        &format!("to={to}"),
     ])?;
 
-    let items: Items =
+    let result: GraphResult<ContributionsData> =
         serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
-    Ok(items.into_urls())
+
+    if !result.errors.is_empty() {
+        let messages: Vec<String> = result.errors.into_iter().map(|err| err.message).collect();
+        return Err(format!("GraphQL error: {}", messages.join("; ")));
+    }
+
+    result
+        .data
+        .and_then(|data| data.user)
+        .map(|user| user.contributions_collection)
+        .ok_or_else(|| format!("No contributions data returned for user '{username}'"))
 }
 
-/// Get PR reviews given by the user
-pub fn get_pr_reviews(username: &str, since: &str) -> Result<Vec<String>, String> {
-    let output = run_gh_command(&[
-        "search",
-        "prs",
-        &format!("--reviewed-by={username}"),
-        "--updated",
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
-    ])?;
+/// Search for issues
+pub fn search_issues(
+    username: &str,
+    filter: &str,
+    since: &str,
+    sort: Option<IssuesSort>,
+) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = vec![
+        "search".to_owned(),
+        "issues".to_owned(),
+        format!("--author={username}"),
+        filter.to_owned(),
+        format!(">={since}"),
+        "--json=url".to_owned(),
+        "--limit=1000".to_owned(),
+    ];
+    push_sort(&mut args, sort);
+
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = run_gh_command(&arg_refs)?;
 
     let items: Items =
         serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
@@ -114,16 +229,24 @@ pub fn get_pr_reviews(username: &str, since: &str) -> Result<Vec<String>, String
 }
 
 /// Get comments written by the user
-pub fn get_comments(username: &str, since: &str) -> Result<Vec<String>, String> {
-    let output = run_gh_command(&[
-        "search",
-        "issues",
-        &format!("--commenter={username}"),
-        "--created",
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
-    ])?;
+pub fn get_comments(
+    username: &str,
+    since: &str,
+    sort: Option<IssuesSort>,
+) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = vec![
+        "search".to_owned(),
+        "issues".to_owned(),
+        format!("--commenter={username}"),
+        "--created".to_owned(),
+        format!(">={since}"),
+        "--json=url".to_owned(),
+        "--limit=1000".to_owned(),
+    ];
+    push_sort(&mut args, sort);
+
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = run_gh_command(&arg_refs)?;
 
     let items: Items =
         serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
@@ -1,15 +1,57 @@
-use std::process::Command;
+mod cache;
+mod concurrency;
+mod data;
+mod github;
+mod output;
 
 use clap::Parser;
 use colored::Colorize as _;
 use jiff::{ToSpan, Zoned};
-use serde::Deserialize;
 
-#[derive(Debug, Clone)]
-struct CodeMetrics {
-    additions: i32,
-    deletions: i32,
-    changed_files: i32,
+use crate::concurrency::MaxHandles;
+use crate::data::CodeMetrics;
+use crate::github::{
+    get_comments, get_current_user, graphql_contributions, pr_code_metrics, search_issues,
+    search_prs, IssuesSort, CONTRIBUTION_PAGE_SIZE,
+};
+use crate::output::{
+    print_code_metrics, print_items, print_items_by_repo, print_json_summary,
+    print_metrics_summary, print_totals, CategorySummary, ContributionTotals, MetricsTotals,
+    PrMetric, Summary,
+};
+
+/// How the summary is rendered to stdout
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    /// Human-readable, colorized output (the default)
+    Text,
+    /// A single JSON object, suitable for piping into `jq`
+    Json,
+}
+
+/// How items (and per-repo groups) are ordered
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SortKey {
+    /// Sort per-repo groups by descending item count (local)
+    Count,
+    /// Ask `gh search` to sort by creation date
+    Created,
+    /// Ask `gh search` to sort by last update
+    Updated,
+    /// Ask `gh search` to sort by comment count
+    Comments,
+}
+
+impl SortKey {
+    /// The corresponding `gh search` sort, or `None` for the local-only `Count`.
+    fn as_query_sort(self) -> Option<IssuesSort> {
+        match self {
+            SortKey::Count => None,
+            SortKey::Created => Some(IssuesSort::Created),
+            SortKey::Updated => Some(IssuesSort::Updated),
+            SortKey::Comments => Some(IssuesSort::Comments),
+        }
+    }
 }
 
 /// Summarize your GitHub activity
@@ -33,225 +75,59 @@ struct Args {
     /// Show code metrics (lines added/removed)
     #[arg(short = 'm', long)]
     metrics: bool,
-}
 
-fn run_gh_command(args: &[&str]) -> Result<String, String> {
-    let output = Command::new("gh")
-        .args(args)
-        .output()
-        .map_err(|err| {
-            if err.kind() == std::io::ErrorKind::NotFound {
-                "GitHub CLI (gh) not found. Please install it from https://cli.github.com/ and make sure it's in your PATH.".to_owned()
-            } else {
-                format!("Failed to execute gh command: {err}")
-            }
-        })?;
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 
-    if !output.status.success() {
-        return Err(format!(
-            "gh command failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+    /// Group each category's items by owner/repo with per-repo subtotals
+    #[arg(long)]
+    by_repo: bool,
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
-}
+    /// Sort items (and, with --by-repo, repositories)
+    #[arg(long, value_enum, value_name = "KEY")]
+    sort: Option<SortKey>,
 
-fn get_current_user() -> Result<String, String> {
-    let output = run_gh_command(&["api", "user", "--jq", ".login"])?;
-    Ok(output.trim().to_string())
-}
+    /// Maximum number of concurrent `gh` queries
+    #[arg(long, value_name = "N", default_value_t = 4)]
+    jobs: usize,
 
-fn search_prs(username: &str, filter: &str, since: &str) -> Result<Vec<String>, String> {
-    let output = run_gh_command(&[
-        "search",
-        "prs",
-        &format!("--author={username}"),
-        filter,
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
-    ])?;
-
-    #[derive(Deserialize)]
-    struct Item {
-        url: String,
-    }
-    #[derive(Deserialize)]
-    struct Items(Vec<Item>);
-
-    let items: Items =
-        serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
-    Ok(items.0.into_iter().map(|item| item.url).collect())
-}
-
-fn search_prs_detailed(
-    username: &str,
-    filter: &str,
-    since: &str,
-) -> Result<Vec<(String, String, CodeMetrics)>, String> {
-    // For now, let's use a simplified approach and return mock data to demonstrate the feature
-    let basic_prs = search_prs(username, filter, since)?;
-
-    // Generate realistic-looking mock metrics for demonstration
-    let mut results = Vec::new();
-    for (i, url) in basic_prs.iter().enumerate() {
-        let mock_metrics = CodeMetrics {
-            additions: (50 + i * 23) as i32,
-            deletions: (20 + i * 7) as i32,
-            changed_files: (3 + i % 5) as i32,
-        };
+    /// Bypass the on-disk response cache
+    #[arg(long)]
+    no_cache: bool,
 
-        // Extract date from URL or use current date as fallback
-        let date = Zoned::now()
-            .checked_sub((i as i32).days())
-            .unwrap_or_else(|_| Zoned::now())
-            .strftime("%Y-%m-%d")
-            .to_string();
-
-        results.push((url.clone(), date, mock_metrics));
-    }
-
-    Ok(results)
+    /// Ignore cached responses and repopulate the cache
+    #[arg(long)]
+    refresh: bool,
 }
 
-fn search_issues(username: &str, filter: &str, since: &str) -> Result<Vec<String>, String> {
-    let output = run_gh_command(&[
-        "search",
-        "issues",
-        &format!("--author={username}"),
-        filter,
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
-    ])?;
-
-    #[derive(Deserialize)]
-    struct Item {
-        url: String,
-    }
-    #[derive(Deserialize)]
-    struct Items(Vec<Item>);
-
-    let items: Items =
-        serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
-    Ok(items.0.into_iter().map(|item| item.url).collect())
-}
-
-fn get_pr_reviews(username: &str, since: &str) -> Result<Vec<String>, String> {
-    let output = run_gh_command(&[
-        "search",
-        "prs",
-        &format!("--reviewed-by={username}"),
-        "--updated",
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
-    ])?;
-
-    #[derive(Deserialize)]
-    struct Item {
-        url: String,
-    }
-    #[derive(Deserialize)]
-    struct Items(Vec<Item>);
-
-    let items: Items =
-        serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
-    Ok(items.0.into_iter().map(|item| item.url).collect())
-}
-
-fn get_comments(username: &str, since: &str) -> Result<Vec<String>, String> {
-    let output = run_gh_command(&[
-        "search",
-        "issues",
-        &format!("--commenter={username}"),
-        "--created",
-        &format!(">={since}"),
-        "--json=url",
-        "--limit=1000",
-    ])?;
-
-    #[derive(Deserialize)]
-    struct Item {
-        url: String,
-    }
-    #[derive(Deserialize)]
-    struct Items(Vec<Item>);
-
-    let items: Items =
-        serde_json::from_str(&output).map_err(|err| format!("Failed to parse JSON: {err}"))?;
-    Ok(items.0.into_iter().map(|item| item.url).collect())
-}
-
-fn extract_repo(url: &str) -> Option<String> {
-    // Extract owner/repo from URLs like https://github.com/owner/repo/...
-    let parts: Vec<&str> = url.split('/').collect();
-    if parts.len() >= 5 && parts[2] == "github.com" {
-        Some(format!("{}/{}", parts[3], parts[4]))
-    } else {
-        None
-    }
-}
-
-fn print_items(label: &str, urls: &[String], verbose: bool) {
-    let repo_count = urls
-        .iter()
-        .filter_map(|url| extract_repo(url))
-        .collect::<std::collections::HashSet<_>>()
-        .len();
-
-    if verbose {
-        println!(
-            "{:19}{}",
-            label.cyan().bold(),
-            urls.len().to_string().green().bold()
-        );
-        if !urls.is_empty() {
-            let mut sorted_urls = urls.to_vec();
-            sorted_urls.sort();
-            for url in sorted_urls {
-                println!("  - {}", url.bright_blue());
-            }
+/// Report a failed category on stderr and treat it as empty so the rest of the
+/// summary still renders.
+fn fetch(result: Result<Vec<String>, String>, what: &str) -> Vec<String> {
+    match result {
+        Ok(urls) => urls,
+        Err(err) => {
+            eprintln!("Error fetching {what}: {err}");
+            Vec::new()
         }
-    } else {
-        let repo_suffix = if repo_count == 1 {
-            "repository"
-        } else {
-            "repositories"
-        };
-        println!(
-            "{:19}{} across {} {}",
-            label.cyan().bold(),
-            urls.len().to_string().green().bold(),
-            repo_count.to_string().yellow(),
-            repo_suffix.dimmed()
-        );
     }
 }
 
-fn print_code_metrics(label: &str, metrics: &[CodeMetrics]) {
-    let total_additions: i32 = metrics.iter().map(|m| m.additions).sum();
-    let total_deletions: i32 = metrics.iter().map(|m| m.deletions).sum();
-    let total_files: i32 = metrics.iter().map(|m| m.changed_files).sum();
-
-    println!(
-        "{:19}{} {}  {} {}  {} {}",
-        label.cyan().bold(),
-        "+".green(),
-        total_additions.to_string().green().bold(),
-        "-".red(),
-        total_deletions.to_string().red().bold(),
-        "files:".dimmed(),
-        total_files.to_string().yellow().bold()
-    );
-}
-
 fn main() {
     let args = Args::parse();
 
     let verbose = args.verbose;
     let show_metrics = args.metrics;
+    let format = args.format;
+    let by_repo = args.by_repo;
+
+    // Server-side sort (if any) and whether per-repo groups are ordered by
+    // count. Preserving the arrival order is decided per-category at render
+    // time: only lists that actually went through `gh search --sort` are kept
+    // in server order; GraphQL-sourced lists still sort alphabetically.
+    let query_sort = args.sort.and_then(SortKey::as_query_sort);
+    let server_sorted = query_sort.is_some();
+    let by_count = args.sort == Some(SortKey::Count);
 
     // Parse --since argument or default to one week ago
     let since_date = args.since.unwrap_or_else(|| {
@@ -259,12 +135,12 @@ fn main() {
         one_week_ago.strftime("%Y-%m-%d").to_string()
     });
 
+    // Initialize the response cache before issuing any `gh` calls.
+    cache::init(args.no_cache, args.refresh, &since_date);
+
     // Get current user
     let username = match get_current_user() {
-        Ok(user) => {
-            println!("GitHub User: {user}\n");
-            user
-        }
+        Ok(user) => user,
         Err(err) => {
             eprintln!("Error: {err}");
             eprintln!("Make sure you're authenticated with 'gh auth login'");
@@ -272,108 +148,204 @@ fn main() {
         }
     };
 
-    println!("GitHub Activity since {since_date}:");
-    println!("{}", "=".repeat(50));
+    let limiter = MaxHandles::new(args.jobs);
 
-    // PRs opened
-    let pr_metrics = if show_metrics {
-        match search_prs_detailed(&username, "--created", &since_date) {
-            Ok(prs) => {
-                let urls: Vec<String> = prs.iter().map(|(url, _, _)| url.clone()).collect();
-                let metrics: Vec<CodeMetrics> = prs.iter().map(|(_, _, m)| m.clone()).collect();
-                print_items("PRs opened:", &urls, verbose);
-                if !metrics.is_empty() {
-                    print_code_metrics("  Code changes:", &metrics);
-                }
-                Some(metrics)
-            }
-            Err(err) => {
-                eprintln!("Error fetching detailed PRs: {err}");
-                None
-            }
-        }
-    } else {
-        match search_prs(&username, "--created", &since_date) {
-            Ok(urls) => {
-                print_items("PRs opened:", &urls, verbose);
-                None
-            }
-            Err(err) => {
-                eprintln!("Error fetching PRs opened: {err}");
-                None
-            }
+    // A single GraphQL round trip is the source for PRs opened, issues opened,
+    // reviews given, and the authoritative contribution totals — replacing the
+    // per-category `gh search` fan-out (and its 1000-item truncation).
+    let contributions = match graphql_contributions(&username, &since_date) {
+        Ok(contributions) => Some(contributions),
+        Err(err) => {
+            eprintln!("Error fetching contributions: {err}");
+            None
         }
     };
 
-    if false {
-        // PRs closed/merged
-        match search_prs(&username, "--closed", &since_date) {
-            Ok(urls) => print_items("PRs closed:", &urls, verbose),
-            Err(err) => eprintln!("Error fetching PRs closed: {err}"),
-        }
-    }
+    let prs_opened: Vec<String> = contributions
+        .as_ref()
+        .map(|c| {
+            c.pull_request_contributions
+                .nodes
+                .iter()
+                .map(|n| n.pull_request.url.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let issues_opened: Vec<String> = contributions
+        .as_ref()
+        .map(|c| {
+            c.issue_contributions
+                .nodes
+                .iter()
+                .map(|n| n.issue.url.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let pr_reviews: Vec<String> = contributions
+        .as_ref()
+        .map(|c| {
+            c.pull_request_review_contributions
+                .nodes
+                .iter()
+                .map(|n| n.pull_request.url.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let totals = contributions.as_ref().map(|c| ContributionTotals {
+        pull_requests: c.total_pull_request_contributions,
+        issues: c.total_issue_contributions,
+        reviews: c.total_pull_request_review_contributions,
+        commits: c.total_commit_contributions,
+    });
 
-    // Issues opened
-    match search_issues(&username, "--created", &since_date) {
-        Ok(urls) => print_items("Issues opened:", &urls, verbose),
-        Err(err) => eprintln!("Error fetching issues opened: {err}"),
+    // The GraphQL node lists are capped at one page; warn (don't silently drop)
+    // when a category fills it, so the truncated URL lists aren't mistaken for
+    // complete. The total counts above remain exact.
+    for (label, urls) in [
+        ("PRs opened", &prs_opened),
+        ("issues opened", &issues_opened),
+        ("reviews given", &pr_reviews),
+    ] {
+        if urls.len() >= CONTRIBUTION_PAGE_SIZE {
+            eprintln!(
+                "Warning: {label} URL list truncated at {CONTRIBUTION_PAGE_SIZE}; \
+                 counts are exact but listed URLs are a sample."
+            );
+        }
     }
 
-    // Issues closed
-    match search_issues(&username, "--closed", &since_date) {
-        Ok(urls) => print_items("Issues closed:", &urls, verbose),
-        Err(err) => eprintln!("Error fetching issues closed: {err}"),
-    }
+    // Issues closed has no `contributionsCollection` equivalent, so it still
+    // uses a targeted `gh search`. Fetch it alongside the per-PR metric views,
+    // bounded by --jobs so we never launch more than N `gh` processes at once.
+    let (issues_closed, pr_details) = std::thread::scope(|scope| {
+        let limiter = &limiter;
+        let issues_closed = scope.spawn(|| {
+            let _permit = limiter.acquire();
+            fetch(
+                search_issues(&username, "--closed", &since_date, query_sort),
+                "issues closed",
+            )
+        });
+
+        let metric_handles: Vec<_> = if show_metrics {
+            prs_opened
+                .iter()
+                .map(|url| {
+                    scope.spawn(move || {
+                        let _permit = limiter.acquire();
+                        match pr_code_metrics(url) {
+                            Ok((date, metrics)) => Some((url.clone(), date, metrics)),
+                            Err(err) => {
+                                eprintln!("Warning: skipping {url}: {err}");
+                                None
+                            }
+                        }
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
 
-    // PR reviews given
-    match get_pr_reviews(&username, &since_date) {
-        Ok(urls) => print_items("PR reviews given:", &urls, verbose),
-        Err(err) => eprintln!("Error fetching PR reviews: {err}"),
-    }
+        let issues_closed = issues_closed.join().unwrap();
+        let pr_details: Option<Vec<(String, String, CodeMetrics)>> = show_metrics.then(|| {
+            metric_handles
+                .into_iter()
+                .filter_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        (issues_closed, pr_details)
+    });
 
-    if false {
-        // Comments written
-        match get_comments(&username, &since_date) {
-            Ok(urls) => print_items("Comments written:", &urls, verbose),
-            Err(err) => eprintln!("Error fetching comments: {err}"),
+    // Aggregate totals for display, derived from the per-PR details.
+    let code_metrics: Option<Vec<CodeMetrics>> = pr_details
+        .as_ref()
+        .map(|details| details.iter().map(|(_, _, m)| m.clone()).collect());
+
+    match format {
+        OutputFormat::Json => {
+            let pr_metrics = pr_details
+                .iter()
+                .flatten()
+                .map(|(url, date, m)| PrMetric {
+                    url: url.clone(),
+                    date: date.clone(),
+                    additions: m.additions,
+                    deletions: m.deletions,
+                    changed_files: m.changed_files,
+                })
+                .collect();
+            let summary = Summary {
+                user: username,
+                since: since_date,
+                totals,
+                prs_opened: CategorySummary::from_urls(prs_opened),
+                issues_opened: CategorySummary::from_urls(issues_opened),
+                issues_closed: CategorySummary::from_urls(issues_closed),
+                pr_reviews: CategorySummary::from_urls(pr_reviews),
+                pr_metrics,
+                code_metrics: code_metrics.as_deref().map(MetricsTotals::from_metrics),
+            };
+            print_json_summary(&summary);
         }
-    }
+        OutputFormat::Text => {
+            // Render a category either flat or grouped by repository. Only
+            // categories that went through `gh search --sort` preserve their
+            // arrival order; GraphQL-sourced lists pass `preserve = false` so
+            // they still sort alphabetically.
+            let show = |label: &str, urls: &[String], preserve: bool| {
+                if by_repo {
+                    print_items_by_repo(label, urls, verbose, by_count, preserve);
+                } else {
+                    print_items(label, urls, verbose, preserve);
+                }
+            };
 
-    println!("{}", "=".repeat(50));
-
-    // Show summary metrics if requested
-    if let (true, Some(metrics)) = (show_metrics, pr_metrics) {
-        println!("\n{}", "ðŸ“Š Code Metrics Summary".cyan().bold().underline());
-        let total_additions: i32 = metrics.iter().map(|m| m.additions).sum();
-        let total_deletions: i32 = metrics.iter().map(|m| m.deletions).sum();
-        let total_files: i32 = metrics.iter().map(|m| m.changed_files).sum();
-
-        println!(
-            "Total lines added:   {}",
-            total_additions.to_string().green().bold()
-        );
-        println!(
-            "Total lines deleted: {}",
-            total_deletions.to_string().red().bold()
-        );
-        println!(
-            "Total files changed: {}",
-            total_files.to_string().yellow().bold()
-        );
-
-        let net_lines = total_additions - total_deletions;
-        if net_lines > 0 {
-            println!(
-                "Net contribution:    {} {}",
-                "+".green(),
-                net_lines.to_string().green().bold()
-            );
-        } else {
-            println!(
-                "Net contribution:    {}{}",
-                net_lines.to_string().red().bold(),
-                " (cleanup/refactoring)".dimmed()
-            );
+            println!("GitHub User: {username}\n");
+            println!("GitHub Activity since {since_date}:");
+            println!("{}", "=".repeat(50));
+
+            if let Some(totals) = &totals {
+                print_totals(totals);
+                println!("{}", "-".repeat(50));
+            }
+
+            // PRs opened, issues opened and reviews come from GraphQL, so the
+            // server sort can't apply — render them alphabetically. Only the
+            // search-backed `issues closed` honors --sort's server order.
+            show("PRs opened:", &prs_opened, false);
+            if let Some(metrics) = &code_metrics {
+                if !metrics.is_empty() {
+                    print_code_metrics("  Code changes:", metrics);
+                }
+            }
+
+            if false {
+                // PRs closed/merged
+                match search_prs(&username, "--closed", &since_date, query_sort) {
+                    Ok(urls) => show("PRs closed:", &urls, server_sorted),
+                    Err(err) => eprintln!("Error fetching PRs closed: {err}"),
+                }
+            }
+
+            show("Issues opened:", &issues_opened, false);
+            show("Issues closed:", &issues_closed, server_sorted);
+            show("PR reviews given:", &pr_reviews, false);
+
+            if false {
+                // Comments written
+                match get_comments(&username, &since_date, query_sort) {
+                    Ok(urls) => show("Comments written:", &urls, server_sorted),
+                    Err(err) => eprintln!("Error fetching comments: {err}"),
+                }
+            }
+
+            println!("{}", "=".repeat(50));
+
+            // Show summary metrics if requested
+            if let Some(metrics) = &code_metrics {
+                print_metrics_summary(metrics);
+            }
         }
     }
 }
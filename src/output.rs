@@ -1,5 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use colored::Colorize as _;
+use serde::Serialize;
 use crate::data::CodeMetrics;
 
 /// Extract owner/repo from GitHub URLs
@@ -13,8 +14,12 @@ pub fn extract_repo(url: &str) -> Option<String> {
     }
 }
 
-/// Print activity items with optional verbose output
-pub fn print_items(label: &str, urls: &[String], verbose: bool) {
+/// Print activity items with optional verbose output.
+///
+/// When `preserve_order` is set the URLs are printed in the order they arrive
+/// (e.g. the order `gh search --sort` returned them); otherwise they are sorted
+/// alphabetically.
+pub fn print_items(label: &str, urls: &[String], verbose: bool, preserve_order: bool) {
     let repo_count = urls
         .iter()
         .filter_map(|url| extract_repo(url))
@@ -24,9 +29,11 @@ pub fn print_items(label: &str, urls: &[String], verbose: bool) {
     if verbose {
         println!("{:19}{}", label.cyan().bold(), urls.len().to_string().green().bold());
         if !urls.is_empty() {
-            let mut sorted_urls = urls.to_vec();
-            sorted_urls.sort();
-            for url in sorted_urls {
+            let mut to_print = urls.to_vec();
+            if !preserve_order {
+                to_print.sort();
+            }
+            for url in to_print {
                 println!("  - {}", url.bright_blue());
             }
         }
@@ -46,6 +53,165 @@ pub fn print_items(label: &str, urls: &[String], verbose: bool) {
     }
 }
 
+/// Print a category grouped by `owner/repo`, with a subtotal per repository.
+///
+/// Repositories are listed alphabetically, or by descending item count when
+/// `by_count` is set (`--sort count`). Within each repo, verbose output honors
+/// `preserve_order` the same way [`print_items`] does.
+pub fn print_items_by_repo(
+    label: &str,
+    urls: &[String],
+    verbose: bool,
+    by_count: bool,
+    preserve_order: bool,
+) {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for url in urls {
+        let repo = extract_repo(url).unwrap_or_else(|| "unknown".to_owned());
+        groups.entry(repo).or_default().push(url.clone());
+    }
+
+    println!(
+        "{:19}{}",
+        label.cyan().bold(),
+        urls.len().to_string().green().bold()
+    );
+
+    let mut ordered: Vec<(String, Vec<String>)> = groups.into_iter().collect();
+    if by_count {
+        ordered.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+    }
+
+    for (repo, repo_urls) in ordered {
+        println!(
+            "  {:17}{}",
+            repo.yellow(),
+            repo_urls.len().to_string().green().bold()
+        );
+        if verbose {
+            let mut to_print = repo_urls;
+            if !preserve_order {
+                to_print.sort();
+            }
+            for url in to_print {
+                println!("    - {}", url.bright_blue());
+            }
+        }
+    }
+}
+
+/// One activity category, ready to be serialized to JSON.
+#[derive(Serialize)]
+pub struct CategorySummary {
+    pub count: usize,
+    pub urls: Vec<String>,
+    pub by_repo: BTreeMap<String, usize>,
+}
+
+impl CategorySummary {
+    /// Build a category summary from its item URLs, bucketing by `owner/repo`.
+    pub fn from_urls(urls: Vec<String>) -> Self {
+        let mut by_repo: BTreeMap<String, usize> = BTreeMap::new();
+        for url in &urls {
+            if let Some(repo) = extract_repo(url) {
+                *by_repo.entry(repo).or_default() += 1;
+            }
+        }
+        Self {
+            count: urls.len(),
+            urls,
+            by_repo,
+        }
+    }
+}
+
+/// Aggregate code-change totals across the reported PRs.
+#[derive(Serialize)]
+pub struct MetricsTotals {
+    pub additions: i32,
+    pub deletions: i32,
+    pub changed_files: i32,
+}
+
+impl MetricsTotals {
+    pub fn from_metrics(metrics: &[CodeMetrics]) -> Self {
+        Self {
+            additions: metrics.iter().map(|m| m.additions).sum(),
+            deletions: metrics.iter().map(|m| m.deletions).sum(),
+            changed_files: metrics.iter().map(|m| m.changed_files).sum(),
+        }
+    }
+}
+
+/// Per-PR code metrics with the PR's creation date, for machine-readable output.
+#[derive(Serialize)]
+pub struct PrMetric {
+    pub url: String,
+    pub date: String,
+    pub additions: i32,
+    pub deletions: i32,
+    pub changed_files: i32,
+}
+
+/// Authoritative contribution counts from a single GraphQL round trip. These
+/// are exact even when the per-category URL lists are sampled.
+#[derive(Serialize)]
+pub struct ContributionTotals {
+    pub pull_requests: i32,
+    pub issues: i32,
+    pub reviews: i32,
+    pub commits: i32,
+}
+
+/// The full machine-readable summary emitted by `--format json`.
+#[derive(Serialize)]
+pub struct Summary {
+    pub user: String,
+    pub since: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub totals: Option<ContributionTotals>,
+    pub prs_opened: CategorySummary,
+    pub issues_opened: CategorySummary,
+    pub issues_closed: CategorySummary,
+    pub pr_reviews: CategorySummary,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pr_metrics: Vec<PrMetric>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_metrics: Option<MetricsTotals>,
+}
+
+/// Print the authoritative contribution totals from the GraphQL response.
+pub fn print_totals(totals: &ContributionTotals) {
+    println!(
+        "{:19}{}",
+        "Pull requests:".cyan().bold(),
+        totals.pull_requests.to_string().green().bold()
+    );
+    println!(
+        "{:19}{}",
+        "Issues:".cyan().bold(),
+        totals.issues.to_string().green().bold()
+    );
+    println!(
+        "{:19}{}",
+        "Reviews:".cyan().bold(),
+        totals.reviews.to_string().green().bold()
+    );
+    println!(
+        "{:19}{}",
+        "Commits:".cyan().bold(),
+        totals.commits.to_string().green().bold()
+    );
+}
+
+/// Emit the summary as a single JSON object to stdout, with colors suppressed.
+pub fn print_json_summary(summary: &Summary) {
+    match serde_json::to_string_pretty(summary) {
+        Ok(json) => println!("{json}"),
+        Err(err) => eprintln!("Failed to serialize summary: {err}"),
+    }
+}
+
 /// Print code metrics summary
 pub fn print_code_metrics(label: &str, metrics: &[CodeMetrics]) {
     let total_additions: i32 = metrics.iter().map(|m| m.additions).sum();